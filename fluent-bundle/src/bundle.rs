@@ -4,8 +4,10 @@
 //! internationalization formatters, functions, environmental variables and are expected to be used
 //! together.
 
-use std::borrow::Cow;
+use std::borrow::{Borrow, Cow};
 use std::collections::hash_map::{Entry as HashEntry, HashMap};
+use std::iter::FromIterator;
+use std::sync::{Arc, RwLock};
 
 use super::entry::{Entry, GetEntry};
 pub use super::errors::FluentError;
@@ -17,20 +19,208 @@ use fluent_locale::{negotiate_languages, NegotiationStrategy};
 use fluent_syntax::ast;
 use intl_pluralrules::{IntlPluralRules, PluralRuleType};
 
+/// Negotiates and builds the `IntlPluralRules` to use for a given locale
+/// fallback chain and rule type (cardinal or ordinal).
+fn build_plural_rules(locales: &[String], rule_type: PluralRuleType) -> IntlPluralRules {
+    let pr_locale = negotiate_languages(
+        locales,
+        IntlPluralRules::get_locales(rule_type),
+        Some("en"),
+        &NegotiationStrategy::Lookup,
+    )[0]
+    .to_owned();
+
+    IntlPluralRules::create(&pr_locale, rule_type).expect("Failed to initialize PluralRules.")
+}
+
+/// Storage for a bundle's `IntlPluralRules`.
+///
+/// `Owned` is used by bundles created with [`FluentBundle::new`], which
+/// eagerly build one cardinal and one ordinal rule set for the bundle's
+/// locale chain.
+/// `Memoized` is used by bundles created with [`FluentBundle::new_concurrent`],
+/// which lazily build and cache a rule set per `(locale, rule type)` behind a
+/// `RwLock` so that several threads formatting through the same bundle at
+/// once share the same constructed formatters instead of racing to rebuild
+/// them.
+///
+/// [`FluentBundle::new`]: ./struct.FluentBundle.html#method.new
+/// [`FluentBundle::new_concurrent`]: ./struct.FluentBundle.html#method.new_concurrent
+enum PluralRulesCache {
+    Owned {
+        cardinal: Arc<IntlPluralRules>,
+        ordinal: Arc<IntlPluralRules>,
+    },
+    Memoized(RwLock<HashMap<(String, PluralRuleType), Arc<IntlPluralRules>>>),
+}
+
+impl PluralRulesCache {
+    fn owned(locales: &[String]) -> Self {
+        PluralRulesCache::Owned {
+            cardinal: Arc::new(build_plural_rules(locales, PluralRuleType::CARDINAL)),
+            ordinal: Arc::new(build_plural_rules(locales, PluralRuleType::ORDINAL)),
+        }
+    }
+
+    fn get(&self, locales: &[String], rule_type: PluralRuleType) -> Arc<IntlPluralRules> {
+        match self {
+            PluralRulesCache::Owned { cardinal, ordinal } => match rule_type {
+                PluralRuleType::ORDINAL => Arc::clone(ordinal),
+                PluralRuleType::CARDINAL => Arc::clone(cardinal),
+            },
+            PluralRulesCache::Memoized(cache) => {
+                let key = (locales.join(","), rule_type);
+                if let Some(pr) = cache.read().expect("plural rules cache poisoned").get(&key) {
+                    return Arc::clone(pr);
+                }
+                let mut cache = cache.write().expect("plural rules cache poisoned");
+                Arc::clone(
+                    cache
+                        .entry(key)
+                        .or_insert_with(|| Arc::new(build_plural_rules(locales, rule_type))),
+                )
+            }
+        }
+    }
+}
+
+/// An ordered, owned collection of named arguments to pass to [`FluentBundle::format`]
+/// or [`FluentBundle::compound`].
+///
+/// Unlike a plain `HashMap<&str, FluentValue>`, `FluentArgs` owns its keys as
+/// well as its values, so it can be built up and stored beyond a single stack
+/// frame instead of being borrowed for the bundle's lifetime, and it iterates
+/// in insertion order, which makes `iter()` useful for debugging/logging a
+/// call's args predictably. This ordering isn't passed through to FTL
+/// functions: named arguments to a `FunctionReference` are still collected
+/// into a plain `HashMap` (see `Entry::Function`) before the function runs.
+///
+/// # Examples
+///
+/// ```
+/// use fluent_bundle::{FluentArgs, FluentValue};
+///
+/// let mut args = FluentArgs::new();
+/// args.set("name", "Rustacean");
+/// args.set("count", 1);
+/// ```
+///
+/// [`FluentBundle::format`]: ./struct.FluentBundle.html#method.format
+/// [`FluentBundle::compound`]: ./struct.FluentBundle.html#method.compound
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct FluentArgs<'args>(Vec<(Cow<'args, str>, FluentValue<'args>)>);
+
+impl<'args> FluentArgs<'args> {
+    /// Creates a new, empty `FluentArgs`.
+    pub fn new() -> Self {
+        FluentArgs(Vec::new())
+    }
+
+    /// Creates a new, empty `FluentArgs` with pre-allocated capacity for
+    /// `capacity` arguments.
+    pub fn with_capacity(capacity: usize) -> Self {
+        FluentArgs(Vec::with_capacity(capacity))
+    }
+
+    /// Sets the value of `key`, overwriting any value it previously held.
+    pub fn set<K, V>(&mut self, key: K, value: V)
+    where
+        K: Into<Cow<'args, str>>,
+        V: Into<FluentValue<'args>>,
+    {
+        let key = key.into();
+        match self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(entry) => entry.1 = value.into(),
+            None => self.0.push((key, value.into())),
+        }
+    }
+
+    /// Returns the value of `key`, if set.
+    pub fn get(&self, key: &str) -> Option<&FluentValue<'args>> {
+        self.0.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+    }
+
+    /// Iterates over the arguments in insertion order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &FluentValue<'args>)> {
+        self.0.iter().map(|(k, v)| (k.as_ref(), v))
+    }
+}
+
+impl<'args, K: Into<Cow<'args, str>>> FromIterator<(K, FluentValue<'args>)> for FluentArgs<'args> {
+    fn from_iter<T: IntoIterator<Item = (K, FluentValue<'args>)>>(iter: T) -> Self {
+        let iter = iter.into_iter();
+        let mut args = FluentArgs::with_capacity(iter.size_hint().0);
+        for (key, value) in iter {
+            args.0.push((key.into(), value));
+        }
+        args
+    }
+}
+
+impl<'args> From<HashMap<&'args str, FluentValue<'args>>> for FluentArgs<'args> {
+    fn from(h: HashMap<&'args str, FluentValue<'args>>) -> Self {
+        h.into_iter().collect()
+    }
+}
+
 #[derive(Debug, PartialEq)]
 pub struct Message<'m> {
     pub value: Option<Cow<'m, str>>,
     pub attributes: HashMap<&'m str, Cow<'m, str>>,
 }
 
+/// A handle to a message looked up in a bundle via [`FluentBundle::get_message`],
+/// giving access to its value and attributes as unresolved AST nodes, without
+/// formatting them.
+///
+/// This is useful for callers that need to inspect a message before rendering
+/// it, e.g. to check whether it has a value at all, to enumerate its
+/// attributes, or to format the same pattern repeatedly (with different args)
+/// via [`FluentBundle::format_pattern`] without re-resolving the message id
+/// each time.
+///
+/// [`FluentBundle::get_message`]: ./struct.FluentBundle.html#method.get_message
+/// [`FluentBundle::format_pattern`]: ./struct.FluentBundle.html#method.format_pattern
+#[derive(Clone, Copy, Debug)]
+pub struct FluentMessage<'m> {
+    id: &'m str,
+    value: Option<&'m ast::Pattern<'m>>,
+    attributes: &'m [ast::Attribute<'m>],
+}
+
+impl<'m> FluentMessage<'m> {
+    /// The message's own id.
+    pub fn id(&self) -> &'m str {
+        self.id
+    }
+
+    /// The message's value pattern, if it has one. Messages made up only of
+    /// attributes (e.g. `login-input =\n    .placeholder = ...`) have no value.
+    pub fn value(&self) -> Option<&'m ast::Pattern<'m>> {
+        self.value
+    }
+
+    /// The message's attributes, e.g. `.title` or `.placeholder`.
+    pub fn attributes(&self) -> &'m [ast::Attribute<'m>] {
+        self.attributes
+    }
+
+    /// Looks up a single attribute's pattern by name.
+    pub fn attribute(&self, name: &str) -> Option<&'m ast::Pattern<'m>> {
+        self.attributes
+            .iter()
+            .find(|attr| attr.id.name == name)
+            .map(|attr| &attr.value)
+    }
+}
+
 /// A collection of localization messages for a single locale, which are meant
 /// to be used together in a single view, widget or any other UI abstraction.
 ///
 /// # Examples
 ///
 /// ```
-/// use fluent_bundle::{FluentBundle, FluentResource, FluentValue};
-/// use std::collections::HashMap;
+/// use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
 ///
 /// let ftl_string = String::from("intro = Welcome, { $name }.");
 /// let resource = FluentResource::try_new(ftl_string)
@@ -40,8 +230,9 @@ pub struct Message<'m> {
 /// bundle.add_resource(&resource)
 ///     .expect("Failed to add FTL resources to the bundle.");
 ///
-/// let mut args = HashMap::new();
-/// args.insert("name", FluentValue::from("Rustacean"));
+/// let mut args = FluentArgs::new();
+/// args.set("name", FluentValue::from("Rustacean"));
+/// bundle.set_use_isolating(false);
 ///
 /// let (value, _) = bundle.format("intro", Some(&args))
 ///     .expect("Failed to format a message.");
@@ -85,7 +276,10 @@ pub struct Message<'m> {
 pub struct FluentBundle<'bundle> {
     pub locales: Vec<String>,
     pub entries: HashMap<String, Entry<'bundle>>,
-    pub plural_rules: IntlPluralRules,
+    plural_rules: PluralRulesCache,
+    pub use_isolating: bool,
+    resources: Vec<Box<dyn Borrow<FluentResource> + Sync + 'bundle>>,
+    transform: Option<fn(&str) -> Cow<str>>,
 }
 
 impl<'bundle> FluentBundle<'bundle> {
@@ -109,23 +303,190 @@ impl<'bundle> FluentBundle<'bundle> {
             .iter()
             .map(std::string::ToString::to_string)
             .collect::<Vec<_>>();
-        let pr_locale = negotiate_languages(
-            &locales,
-            IntlPluralRules::get_locales(PluralRuleType::CARDINAL),
-            Some("en"),
-            &NegotiationStrategy::Lookup,
-        )[0]
-        .to_owned();
-
-        let pr = IntlPluralRules::create(&pr_locale, PluralRuleType::CARDINAL)
-            .expect("Failed to initialize PluralRules.");
+        let plural_rules = PluralRulesCache::owned(&locales);
+        FluentBundle {
+            locales,
+            entries: HashMap::new(),
+            plural_rules,
+            use_isolating: true,
+            resources: vec![],
+            transform: None,
+        }
+    }
+
+    /// Constructs a `FluentBundle` suitable for sharing across threads that
+    /// format concurrently through it, e.g. behind an `Arc<FluentBundle>`.
+    ///
+    /// Unlike [`FluentBundle::new`], which eagerly builds its `IntlPluralRules`
+    /// once up front, a concurrent bundle builds its international formatters
+    /// lazily on first use and caches them behind a thread-safe memoizer keyed
+    /// by locale, so repeated `format`/`compound` calls from multiple threads
+    /// reuse the same constructed formatter instead of racing to rebuild it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluent_bundle::FluentBundle;
+    ///
+    /// let bundle = FluentBundle::new_concurrent(&["en-US"]);
+    /// ```
+    ///
+    /// Several threads formatting concurrently through the same bundle, each
+    /// triggering the lazy construction and sharing the memoized formatter:
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+    ///
+    /// let ftl_string = String::from("intro = Welcome, { $name }.");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new_concurrent(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    ///
+    /// std::thread::scope(|scope| {
+    ///     for name in ["Alice", "Bob", "Carol"] {
+    ///         let bundle = &bundle;
+    ///         scope.spawn(move || {
+    ///             let mut args = FluentArgs::new();
+    ///             args.set("name", FluentValue::from(name));
+    ///             let (value, _) = bundle.format("intro", Some(&args))
+    ///                 .expect("Failed to format a message.");
+    ///             assert_eq!(value, format!("Welcome, \u{2068}{}\u{2069}.", name));
+    ///         });
+    ///     }
+    /// });
+    /// ```
+    ///
+    /// [`FluentBundle::new`]: ./struct.FluentBundle.html#method.new
+    pub fn new_concurrent<'a, S: ToString>(locales: &'a [S]) -> FluentBundle<'bundle> {
+        let locales = locales
+            .iter()
+            .map(std::string::ToString::to_string)
+            .collect::<Vec<_>>();
         FluentBundle {
             locales,
             entries: HashMap::new(),
-            plural_rules: pr,
+            plural_rules: PluralRulesCache::Memoized(RwLock::new(HashMap::new())),
+            use_isolating: true,
+            resources: vec![],
+            transform: None,
         }
     }
 
+    /// Returns the `IntlPluralRules` (cardinal category selection) to use for
+    /// this bundle's locale chain, building and memoizing it lazily if this
+    /// bundle was constructed with [`FluentBundle::new_concurrent`].
+    ///
+    /// [`FluentBundle::new_concurrent`]: ./struct.FluentBundle.html#method.new_concurrent
+    pub(crate) fn cardinal_plural_rules(&self) -> Arc<IntlPluralRules> {
+        self.plural_rules.get(&self.locales, PluralRuleType::CARDINAL)
+    }
+
+    /// Returns the `IntlPluralRules` (ordinal category selection, e.g.
+    /// "1st"/"2nd"/"3rd") to use for this bundle's locale chain, building and
+    /// memoizing it lazily if this bundle was constructed with
+    /// [`FluentBundle::new_concurrent`].
+    ///
+    /// The resolver selects against this rule set, instead of the cardinal
+    /// one, when a number argument carries an `ORDINAL` formatting hint.
+    ///
+    /// [`FluentBundle::new_concurrent`]: ./struct.FluentBundle.html#method.new_concurrent
+    pub(crate) fn ordinal_plural_rules(&self) -> Arc<IntlPluralRules> {
+        self.plural_rules.get(&self.locales, PluralRuleType::ORDINAL)
+    }
+
+    /// Sets whether or not this bundle should wrap substituted placeables
+    /// (variables, message references and function results) in Unicode
+    /// isolation marks (`FSI`/`PDI`, `U+2068`/`U+2069`) when resolving a
+    /// pattern. This allows strings interpolated from a different
+    /// directionality than the surrounding message to render correctly,
+    /// and is enabled by default. A pattern made up of a single placeable
+    /// and nothing else is not wrapped.
+    ///
+    /// Consumers who post-process the formatted output themselves, or who
+    /// know their messages never mix directionality, may want to disable
+    /// this to get back the raw concatenated string.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+    ///
+    /// let ftl_string = String::from("intro = Welcome, { $name }.");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    ///
+    /// let mut args = FluentArgs::new();
+    /// args.set("name", FluentValue::from("Rustacean"));
+    ///
+    /// let (value, _) = bundle.format("intro", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "Welcome, \u{2068}Rustacean\u{2069}.");
+    ///
+    /// bundle.set_use_isolating(false);
+    /// let (value, _) = bundle.format("intro", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "Welcome, Rustacean.");
+    /// ```
+    pub fn set_use_isolating(&mut self, value: bool) {
+        self.use_isolating = value;
+    }
+
+    /// Registers a post-resolution text transform, applied to each literal
+    /// `TextElement` of a pattern as it is resolved, but not to interpolated
+    /// argument values. The literal text of a referenced message or term is
+    /// resolved the same way, so it is transformed as well. This is meant for
+    /// pseudolocalization: testing that a UI's layout and the translation
+    /// pipeline itself survive strings that look nothing like the source
+    /// text, without having to write a real translation.
+    ///
+    /// A typical transform accents and elongates Latin text (e.g. "Welcome"
+    /// becomes "Ŵêļċömê") and may wrap it in markers so untranslated or
+    /// hard-coded strings stand out; a "flipped" variant reverses characters
+    /// for right-to-left bidi testing. Pass `None` to disable.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentBundle, FluentResource, FluentValue};
+    /// use std::borrow::Cow;
+    ///
+    /// fn shout(s: &str) -> Cow<str> {
+    ///     Cow::from(s.to_uppercase())
+    /// }
+    ///
+    /// let ftl_string = String::from("intro = Welcome, { $name }.");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    /// bundle.set_transform(Some(shout));
+    ///
+    /// let mut args = fluent_bundle::FluentArgs::new();
+    /// args.set("name", FluentValue::from("Rustacean"));
+    ///
+    /// let (value, _) = bundle.format("intro", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// // Only the literal text is transformed; the interpolated name isn't.
+    /// assert_eq!(&value, "WELCOME, \u{2068}Rustacean\u{2069}.");
+    /// ```
+    pub fn set_transform(&mut self, transform: Option<fn(&str) -> Cow<str>>) {
+        self.transform = transform;
+    }
+
+    /// Returns the transform registered with [`set_transform`], if any, for
+    /// the resolver to apply to literal text as it walks a pattern.
+    ///
+    /// [`set_transform`]: ./struct.FluentBundle.html#method.set_transform
+    pub(crate) fn transform(&self) -> Option<fn(&str) -> Cow<str>> {
+        self.transform
+    }
+
     /// Returns true if this bundle contains a message with the given id.
     ///
     /// # Examples
@@ -227,10 +588,59 @@ impl<'bundle> FluentBundle<'bundle> {
     /// [FTL syntax]: https://projectfluent.org/fluent/guide/
     /// [`indoc!`]: https://github.com/dtolnay/indoc
     /// [`Result<T>`]: https://doc.rust-lang.org/std/result/enum.Result.html
+    ///
+    /// # Ownership
+    ///
+    /// This borrows `res` for `'bundle`, the same zero-cost path the bundle
+    /// has always used; it does not itself accept `Borrow<FluentResource>`
+    /// (a `FluentResource` or `Arc<FluentResource>`; `Rc<FluentResource>` is
+    /// not accepted by either method) —
+    /// for that, see [`add_resource_owned`], which takes ownership of `res`
+    /// instead, e.g. to share a single parsed resource across several
+    /// bundles, or let a bundle own resources it parsed itself.
+    ///
+    /// [`add_resource_owned`]: ./struct.FluentBundle.html#method.add_resource_owned
     pub fn add_resource(&mut self, res: &'bundle FluentResource) -> Result<(), Vec<FluentError>> {
+        self.insert_resource_entries(res)
+    }
+
+    /// Like [`add_resource`], but takes ownership of `res` instead of
+    /// borrowing it, accepting anything that implements `Borrow<FluentResource>`
+    /// — a `FluentResource` or an `Arc<FluentResource>` — and keeping it
+    /// alive for as long as the bundle itself. `Rc<FluentResource>` does not
+    /// qualify: this bundle must be safe to share across threads (see
+    /// [`FluentBundle::new_concurrent`]), so the bound requires `Sync`, which
+    /// `Rc` never implements. Prefer [`add_resource`] for the common case of
+    /// a `&FluentResource` the caller already owns; reach for this when a
+    /// resource needs to be shared across several bundles or owned by the
+    /// bundle that parsed it.
+    ///
+    /// [`add_resource`]: ./struct.FluentBundle.html#method.add_resource
+    /// [`FluentBundle::new_concurrent`]: ./struct.FluentBundle.html#method.new_concurrent
+    pub fn add_resource_owned<R>(&mut self, res: R) -> Result<(), Vec<FluentError>>
+    where
+        R: Borrow<FluentResource> + Sync + 'bundle,
+    {
+        let res: Box<dyn Borrow<FluentResource> + Sync + 'bundle> = Box::new(res);
+        // SAFETY: `res` is heap-allocated and moved, unmodified, into
+        // `self.resources` below, so the address of the `FluentResource` it
+        // borrows does not change for the remaining lifetime of `self`. Since
+        // `self.entries` (which will borrow into it) and `self.resources`
+        // (which keeps it alive) are dropped together when `self` is dropped,
+        // it is sound to treat the borrow as living for `'bundle`.
+        let res_ref: &'bundle FluentResource =
+            unsafe { &*((*res).borrow() as *const FluentResource) };
+        self.resources.push(res);
+        self.insert_resource_entries(res_ref)
+    }
+
+    fn insert_resource_entries(
+        &mut self,
+        res_ref: &'bundle FluentResource,
+    ) -> Result<(), Vec<FluentError>> {
         let mut errors = vec![];
 
-        for entry in &res.ast().body {
+        for entry in &res_ref.ast().body {
             let id = match entry {
                 ast::ResourceEntry::Entry(ast::Entry::Message(ast::Message { ref id, .. }))
                 | ast::ResourceEntry::Entry(ast::Entry::Term(ast::Term { ref id, .. })) => id.name,
@@ -265,6 +675,94 @@ impl<'bundle> FluentBundle<'bundle> {
         }
     }
 
+    /// Looks up the message with the given `id`, returning a [`FluentMessage`]
+    /// handle that exposes its value and attributes as unresolved AST nodes,
+    /// without formatting them. This is useful for widgets that need to know
+    /// whether a message has a value, or enumerate its attributes, before
+    /// rendering it; pass the patterns it returns to [`format_pattern`] to
+    /// render them.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentBundle, FluentResource};
+    ///
+    /// let ftl_string = String::from("
+    /// hello =
+    ///     .title = Hi!
+    ///     .tooltip = This says 'Hi!'
+    /// ");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    ///
+    /// let message = bundle.get_message("hello").expect("Message doesn't exist.");
+    /// assert_eq!(message.value(), None);
+    /// assert_eq!(message.attributes().len(), 2);
+    /// ```
+    ///
+    /// [`FluentMessage`]: ./struct.FluentMessage.html
+    /// [`format_pattern`]: ./struct.FluentBundle.html#method.format_pattern
+    pub fn get_message(&self, id: &str) -> Option<FluentMessage<'bundle>> {
+        let message = self.entries.get_message(id)?;
+        Some(FluentMessage {
+            id: message.id.name,
+            value: message.value.as_ref(),
+            attributes: &message.attributes,
+        })
+    }
+
+    /// Formats an already-resolved `pattern`, e.g. one obtained from
+    /// [`FluentMessage::value`] or [`FluentMessage::attribute`], using `args`
+    /// to provide variables. Any errors encountered are appended to `errors`.
+    ///
+    /// This is the low-level primitive that [`format`] and [`compound`] are
+    /// built on; use it directly when you already looked a message up via
+    /// [`get_message`] and want to format the same pattern more than once, or
+    /// format an attribute without re-resolving the message id.
+    ///
+    /// [`FluentMessage::value`]: ./struct.FluentMessage.html#method.value
+    /// [`FluentMessage::attribute`]: ./struct.FluentMessage.html#method.attribute
+    /// [`format`]: ./struct.FluentBundle.html#method.format
+    /// [`compound`]: ./struct.FluentBundle.html#method.compound
+    /// [`get_message`]: ./struct.FluentBundle.html#method.get_message
+    pub fn format_pattern(
+        &'bundle self,
+        pattern: &'bundle ast::Pattern<'bundle>,
+        args: Option<&'bundle FluentArgs>,
+        errors: &mut Vec<FluentError>,
+    ) -> Cow<'bundle, str> {
+        // Callers of this low-level entry point didn't resolve the pattern
+        // through a message id, so there's no real id to pass as the entry.
+        self.resolve_pattern(pattern, DisplayableNode::new("???", None), args, errors)
+    }
+
+    /// Resolves `pattern` the same way [`format_pattern`] does, but passes
+    /// `entry` instead of the generic placeholder, for callers ([`format`],
+    /// [`compound`]) that know the real message id (and, for attributes,
+    /// attribute name) they're formatting. The errors collected do not record
+    /// `entry`.
+    ///
+    /// [`format_pattern`]: ./struct.FluentBundle.html#method.format_pattern
+    /// [`format`]: ./struct.FluentBundle.html#method.format
+    /// [`compound`]: ./struct.FluentBundle.html#method.compound
+    fn resolve_pattern(
+        &'bundle self,
+        pattern: &'bundle ast::Pattern<'bundle>,
+        entry: DisplayableNode<'bundle>,
+        args: Option<&'bundle FluentArgs>,
+        errors: &mut Vec<FluentError>,
+    ) -> Cow<'bundle, str> {
+        let mut env = Scope::new(self, args);
+        let value = resolve_value_for_entry(pattern, entry, &mut env);
+        for err in env.errors {
+            errors.push(err.into());
+        }
+        value
+    }
+
     /// Formats the message value identified by `path` using `args` to
     /// provide variables. `path` is either a message id ("hello"), or
     /// message id plus attribute ("hello.tooltip").
@@ -272,8 +770,7 @@ impl<'bundle> FluentBundle<'bundle> {
     /// # Examples
     ///
     /// ```
-    /// use fluent_bundle::{FluentBundle, FluentResource, FluentValue};
-    /// use std::collections::HashMap;
+    /// use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
     ///
     /// let ftl_string = String::from("intro = Welcome, { $name }.");
     /// let resource = FluentResource::try_new(ftl_string)
@@ -282,8 +779,9 @@ impl<'bundle> FluentBundle<'bundle> {
     /// bundle.add_resource(&resource)
     ///     .expect("Failed to add FTL resources to the bundle.");
     ///
-    /// let mut args = HashMap::new();
-    /// args.insert("name", FluentValue::from("Rustacean"));
+    /// let mut args = FluentArgs::new();
+    /// args.set("name", FluentValue::from("Rustacean"));
+    /// bundle.set_use_isolating(false);
     ///
     /// let (value, _) = bundle.format("intro", Some(&args))
     ///     .expect("Failed to format a message.");
@@ -342,50 +840,93 @@ impl<'bundle> FluentBundle<'bundle> {
     ///     .expect("Failed to format a message.");
     /// assert_eq!(&value, "a foo b");
     /// ```
+    ///
+    /// Selecting a variant by CLDR cardinal plural category:
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentArgs, FluentBundle, FluentResource, FluentValue};
+    ///
+    /// let ftl_string = String::from("
+    /// emails = { $count ->
+    ///     [one] You have one new email.
+    ///    *[other] You have { $count } new emails.
+    /// }
+    /// ");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    ///
+    /// let mut args = FluentArgs::new();
+    /// args.set("count", FluentValue::from(1));
+    /// let (value, _) = bundle.format("emails", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "You have one new email.");
+    ///
+    /// let mut args = FluentArgs::new();
+    /// args.set("count", FluentValue::from(5));
+    /// let (value, _) = bundle.format("emails", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "You have \u{2068}5\u{2069} new emails.");
+    /// ```
+    ///
+    /// Selecting a variant by CLDR ordinal plural category, via
+    /// [`FluentNumber::as_ordinal`]:
+    ///
+    /// ```
+    /// use fluent_bundle::{FluentArgs, FluentBundle, FluentNumber, FluentResource, FluentValue};
+    ///
+    /// let ftl_string = String::from("
+    /// position = { $pos ->
+    ///     [one] { $pos }st
+    ///     [two] { $pos }nd
+    ///     [few] { $pos }rd
+    ///    *[other] { $pos }th
+    /// }
+    /// ");
+    /// let resource = FluentResource::try_new(ftl_string)
+    ///     .expect("Could not parse an FTL string.");
+    /// let mut bundle = FluentBundle::new(&["en-US"]);
+    /// bundle.add_resource(&resource)
+    ///     .expect("Failed to add FTL resources to the bundle.");
+    ///
+    /// let mut args = FluentArgs::new();
+    /// args.set("pos", FluentValue::from(FluentNumber::new(1.0).as_ordinal()));
+    /// let (value, _) = bundle.format("position", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "\u{2068}1\u{2069}st");
+    ///
+    /// let mut args = FluentArgs::new();
+    /// args.set("pos", FluentValue::from(FluentNumber::new(3.0).as_ordinal()));
+    /// let (value, _) = bundle.format("position", Some(&args))
+    ///     .expect("Failed to format a message.");
+    /// assert_eq!(&value, "\u{2068}3\u{2069}rd");
+    /// ```
+    ///
+    /// [`FluentNumber::as_ordinal`]: ./struct.FluentNumber.html#method.as_ordinal
     pub fn format(
         &'bundle self,
         path: &str,
-        args: Option<&'bundle HashMap<&str, FluentValue>>,
+        args: Option<&'bundle FluentArgs>,
     ) -> Option<(Cow<'bundle, str>, Vec<FluentError>)> {
-        let mut env = Scope::new(self, args);
-
         let mut errors = vec![];
 
-        let string = if let Some(ptr_pos) = path.find('.') {
+        let (pattern, entry) = if let Some(ptr_pos) = path.find('.') {
             let message_id = &path[..ptr_pos];
-            let message = self.entries.get_message(message_id)?;
             let attr_name = &path[(ptr_pos + 1)..];
-            let attr = message
-                .attributes
-                .iter()
-                .find(|attr| attr.id.name == attr_name)?;
-            resolve_value_for_entry(
-                &attr.value,
-                DisplayableNode::new(message.id.name, Some(attr.id.name)),
-                &mut env,
-            )
-            .to_string()
+            let message = self.get_message(message_id)?;
+            let attr = message.attributes().iter().find(|attr| attr.id.name == attr_name)?;
+            (&attr.value, DisplayableNode::new(message.id(), Some(attr.id.name)))
         } else {
-            let message_id = path;
-            let message = self.entries.get_message(message_id)?;
-            message
-                .value
-                .as_ref()
-                .map(|value| {
-                    resolve_value_for_entry(
-                        value,
-                        DisplayableNode::new(message.id.name, None),
-                        &mut env,
-                    )
-                })?
-                .to_string()
+            let message = self.get_message(path)?;
+            let pattern = message.value()?;
+            (pattern, DisplayableNode::new(message.id(), None))
         };
 
-        for err in env.errors {
-            errors.push(err.into());
-        }
+        let value = self.resolve_pattern(pattern, entry, args, &mut errors);
 
-        Some((string, errors))
+        Some((value, errors))
     }
 
     /// Formats both the message value and attributes identified by `message_id`
@@ -396,8 +937,7 @@ impl<'bundle> FluentBundle<'bundle> {
     /// # Examples
     ///
     /// ```
-    /// use fluent_bundle::{FluentBundle, FluentResource, FluentValue};
-    /// use std::collections::HashMap;
+    /// use fluent_bundle::{FluentBundle, FluentResource};
     ///
     /// let ftl_string = String::from("
     /// login-input = Predefined value
@@ -433,32 +973,132 @@ impl<'bundle> FluentBundle<'bundle> {
     pub fn compound(
         &'bundle self,
         message_id: &str,
-        args: Option<&'bundle HashMap<&str, FluentValue>>,
+        args: Option<&'bundle FluentArgs>,
     ) -> Option<(Message<'bundle>, Vec<FluentError>)> {
-        let mut env = Scope::new(self, args);
         let mut errors = vec![];
-        let message = self.entries.get_message(message_id)?;
+        let message = self.get_message(message_id)?;
 
-        let value = message.value.as_ref().map(|value| {
-            resolve_value_for_entry(value, DisplayableNode::new(message.id.name, None), &mut env)
-                .to_string()
+        let value = message.value().map(|pattern| {
+            let entry = DisplayableNode::new(message.id(), None);
+            self.resolve_pattern(pattern, entry, args, &mut errors)
         });
 
         let mut attributes = HashMap::new();
 
-        for attr in message.attributes.iter() {
-            let val = resolve_value_for_entry(
-                &attr.value,
-                DisplayableNode::new(message.id.name, Some(attr.id.name)),
-                &mut env,
-            );
-            attributes.insert(attr.id.name, val.to_string());
+        for attr in message.attributes() {
+            let entry = DisplayableNode::new(message.id(), Some(attr.id.name));
+            let val = self.resolve_pattern(&attr.value, entry, args, &mut errors);
+            attributes.insert(attr.id.name, val);
         }
 
-        for err in env.errors {
-            errors.push(err.into());
+        Some((Message { value, attributes }, errors))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn args_set_overwrites_and_preserves_insertion_order() {
+        let mut args = FluentArgs::new();
+        args.set("name", "Rustacean");
+        args.set("count", 1);
+        args.set("name", "Ferris");
+
+        let values: Vec<_> = args.iter().map(|(k, _)| k).collect();
+        assert_eq!(values, vec!["name", "count"]);
+        assert_eq!(args.get("name"), Some(&FluentValue::from("Ferris")));
+    }
+
+    #[test]
+    fn ordinal_hint_picks_the_ordinal_variant_even_when_a_same_named_cardinal_variant_could_match() {
+        use super::super::types::FluentNumber;
+
+        // "one" is a valid category for both cardinal and ordinal rules in
+        // en-US, but 1 is cardinal-"one" while 21 is ordinal-"one" ("21st").
+        // Only the `as_ordinal()` hint should route 21 to the ordinal variant.
+        let ftl_string = String::from(
+            "
+pos = { $n ->
+    [one] ordinal-one
+   *[other] other
+}
+",
+        );
+        let resource = FluentResource::try_new(ftl_string).expect("Could not parse an FTL string.");
+        let mut bundle = FluentBundle::new(&["en-US"]);
+        bundle
+            .add_resource(&resource)
+            .expect("Failed to add FTL resources to the bundle.");
+
+        let mut args = FluentArgs::new();
+        args.set("n", FluentValue::from(FluentNumber::new(21.0).as_ordinal()));
+        let (value, _) = bundle.format("pos", Some(&args)).expect("Failed to format a message.");
+        assert_eq!(&value, "ordinal-one");
+
+        let mut args = FluentArgs::new();
+        args.set("n", FluentValue::from(FluentNumber::new(21.0)));
+        let (value, _) = bundle.format("pos", Some(&args)).expect("Failed to format a message.");
+        assert_eq!(&value, "other");
+    }
+
+    #[test]
+    fn transform_applies_only_to_literal_text_not_to_isolated_placeables() {
+        fn shout(s: &str) -> Cow<str> {
+            Cow::from(s.to_uppercase())
         }
 
-        Some((Message { value, attributes }, errors))
+        let ftl_string = String::from("intro = Welcome, { $name }.");
+        let resource = FluentResource::try_new(ftl_string).expect("Could not parse an FTL string.");
+        let mut bundle = FluentBundle::new(&["en-US"]);
+        bundle
+            .add_resource(&resource)
+            .expect("Failed to add FTL resources to the bundle.");
+        bundle.set_transform(Some(shout));
+
+        let mut args = FluentArgs::new();
+        args.set("name", FluentValue::from("rustacean"));
+        let (value, _) = bundle.format("intro", Some(&args)).expect("Failed to format a message.");
+
+        // "Welcome, " is transformed; the isolated placeable value is not.
+        assert_eq!(&value, "WELCOME, \u{2068}rustacean\u{2069}.");
+    }
+
+    #[test]
+    fn memoized_plural_rules_are_consistent_under_concurrent_contention() {
+        let ftl_string = String::from(
+            "
+emails = { $count ->
+    [one] one email
+   *[other] { $count } emails
+}
+",
+        );
+        let resource = FluentResource::try_new(ftl_string).expect("Could not parse an FTL string.");
+        let mut bundle = FluentBundle::new_concurrent(&["en-US"]);
+        bundle
+            .add_resource(&resource)
+            .expect("Failed to add FTL resources to the bundle.");
+
+        std::thread::scope(|scope| {
+            for _ in 0..8 {
+                let bundle = &bundle;
+                scope.spawn(move || {
+                    for count in 1..20 {
+                        let mut args = FluentArgs::new();
+                        args.set("count", FluentValue::from(count));
+                        let (value, _) =
+                            bundle.format("emails", Some(&args)).expect("Failed to format a message.");
+                        let expected = if count == 1 {
+                            "one email".to_string()
+                        } else {
+                            format!("\u{2068}{}\u{2069} emails", count)
+                        };
+                        assert_eq!(value, expected);
+                    }
+                });
+            }
+        });
     }
 }
@@ -0,0 +1,47 @@
+//! The per-id registry a bundle keeps: a message, a term, or a function,
+//! looked up by the identifier it was registered under.
+
+use std::collections::HashMap;
+
+use fluent_syntax::ast;
+
+use super::types::FluentValue;
+
+pub enum Entry<'bundle> {
+    Message(&'bundle ast::Message<'bundle>),
+    Term(&'bundle ast::Term<'bundle>),
+    Function(
+        Box<
+            dyn for<'a> Fn(&[FluentValue<'a>], &HashMap<&str, FluentValue<'a>>) -> FluentValue<'a>
+                + Sync
+                + Send
+                + 'bundle,
+        >,
+    ),
+}
+
+pub trait GetEntry<'bundle> {
+    fn get_entry(&self, id: &str) -> Option<&Entry<'bundle>>;
+    fn get_message(&self, id: &str) -> Option<&'bundle ast::Message<'bundle>>;
+    fn get_term(&self, id: &str) -> Option<&'bundle ast::Term<'bundle>>;
+}
+
+impl<'bundle> GetEntry<'bundle> for HashMap<String, Entry<'bundle>> {
+    fn get_entry(&self, id: &str) -> Option<&Entry<'bundle>> {
+        self.get(id)
+    }
+
+    fn get_message(&self, id: &str) -> Option<&'bundle ast::Message<'bundle>> {
+        match self.get_entry(id) {
+            Some(Entry::Message(message)) => Some(message),
+            _ => None,
+        }
+    }
+
+    fn get_term(&self, id: &str) -> Option<&'bundle ast::Term<'bundle>> {
+        match self.get_entry(id) {
+            Some(Entry::Term(term)) => Some(term),
+            _ => None,
+        }
+    }
+}
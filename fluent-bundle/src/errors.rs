@@ -0,0 +1,30 @@
+//! Errors surfaced by a `FluentBundle`, either while adding resources or
+//! while resolving a pattern.
+
+use std::error::Error;
+use std::fmt;
+
+use super::resolve::ResolverError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FluentError {
+    ResolverError(ResolverError),
+    Overriding { kind: &'static str, id: String },
+}
+
+impl fmt::Display for FluentError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FluentError::ResolverError(err) => write!(f, "resolver error: {}", err),
+            FluentError::Overriding { kind, id } => write!(f, "overriding {}: {}", kind, id),
+        }
+    }
+}
+
+impl Error for FluentError {}
+
+impl From<ResolverError> for FluentError {
+    fn from(error: ResolverError) -> Self {
+        FluentError::ResolverError(error)
+    }
+}
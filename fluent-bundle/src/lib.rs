@@ -0,0 +1,16 @@
+//! `fluent-bundle` is a collection of structures for managing a single
+//! localization context, formatting messages from FTL resources using
+//! runtime values.
+
+mod bundle;
+mod entry;
+mod errors;
+mod resolve;
+mod resource;
+mod types;
+
+pub use bundle::{FluentArgs, FluentBundle, FluentMessage, Message};
+pub use errors::FluentError;
+pub use resolve::ResolverError;
+pub use resource::FluentResource;
+pub use types::{DisplayableNode, FluentNumber, FluentValue};
@@ -0,0 +1,313 @@
+//! The pattern resolver: walks a `Pattern`'s `TextElement`s and `Placeable`s
+//! and turns them into the final formatted string.
+
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+
+use fluent_syntax::ast;
+
+use super::bundle::{FluentArgs, FluentBundle};
+use super::entry::{Entry, GetEntry};
+use super::types::{DisplayableNode, FluentValue};
+
+const MAX_PLACEABLES: u8 = 100;
+
+/// An error produced while resolving a pattern to a string. Resolution never
+/// fails outright; the resolver falls back to `???` (or, for a reference
+/// cycle, to the referenced id) and records the error here instead, so a caller always gets a best-effort
+/// string back alongside the list of things that went wrong.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ResolverError {
+    Reference(String),
+    MissingDefault,
+    Cyclic(String),
+    TooManyPlaceables,
+}
+
+impl fmt::Display for ResolverError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ResolverError::Reference(id) => write!(f, "unknown reference: {}", id),
+            ResolverError::MissingDefault => write!(f, "no default variant"),
+            ResolverError::Cyclic(id) => write!(f, "cyclic reference to {}", id),
+            ResolverError::TooManyPlaceables => write!(f, "too many placeables"),
+        }
+    }
+}
+
+/// Per-call state threaded through pattern resolution: the bundle being
+/// formatted from, the args supplied by the caller, the errors accumulated so
+/// far, and a guard against message/term reference cycles.
+pub struct Scope<'bundle> {
+    pub bundle: &'bundle FluentBundle<'bundle>,
+    pub args: Option<&'bundle FluentArgs<'bundle>>,
+    pub errors: Vec<ResolverError>,
+    travelled: RefCell<Vec<&'bundle str>>,
+    placeables: u8,
+}
+
+impl<'bundle> Scope<'bundle> {
+    pub fn new(bundle: &'bundle FluentBundle<'bundle>, args: Option<&'bundle FluentArgs<'bundle>>) -> Self {
+        Scope {
+            bundle,
+            args,
+            errors: vec![],
+            travelled: RefCell::new(vec![]),
+            placeables: 0,
+        }
+    }
+}
+
+/// Resolves `pattern` (the value of a message or attribute identified by
+/// `entry`) to its formatted string. `entry` is passed down through nested
+/// resolution; the errors recorded in `scope` do not include it.
+pub fn resolve_value_for_entry<'bundle>(
+    pattern: &'bundle ast::Pattern<'bundle>,
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> Cow<'bundle, str> {
+    if let [ast::PatternElement::TextElement(text)] = pattern.elements.as_slice() {
+        return apply_transform(scope, text);
+    }
+
+    let mut result = String::new();
+    for element in &pattern.elements {
+        match element {
+            ast::PatternElement::TextElement(text) => result.push_str(&apply_transform(scope, text)),
+            ast::PatternElement::Placeable(expression) => {
+                scope.placeables += 1;
+                if scope.placeables > MAX_PLACEABLES {
+                    scope.errors.push(ResolverError::TooManyPlaceables);
+                    result.push('�');
+                    continue;
+                }
+
+                let value = resolve_expression(expression, entry, scope);
+                let formatted = value.as_string();
+
+                if scope.bundle.use_isolating && pattern.elements.len() > 1 {
+                    result.push('\u{2068}');
+                    result.push_str(&formatted);
+                    result.push('\u{2069}');
+                } else {
+                    result.push_str(&formatted);
+                }
+            }
+        }
+    }
+
+    Cow::Owned(result)
+}
+
+/// Applies the bundle's registered transform (see
+/// `FluentBundle::set_transform`) to a literal `TextElement`, if one is set;
+/// otherwise returns `text` unchanged. Never applied to interpolated values.
+fn apply_transform<'bundle>(scope: &Scope<'bundle>, text: &'bundle str) -> Cow<'bundle, str> {
+    match scope.bundle.transform() {
+        Some(transform) => Cow::Owned(transform(text).into_owned()),
+        None => Cow::Borrowed(text),
+    }
+}
+
+fn resolve_expression<'bundle>(
+    expression: &'bundle ast::Expression<'bundle>,
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    match expression {
+        ast::Expression::InlineExpression(inline) => resolve_inline_expression(inline, entry, scope),
+        ast::Expression::SelectExpression { selector, variants } => {
+            let selector = resolve_inline_expression(selector, entry, scope);
+            resolve_select_expression(&selector, variants, entry, scope)
+        }
+    }
+}
+
+fn resolve_select_expression<'bundle>(
+    selector: &FluentValue<'bundle>,
+    variants: &'bundle [ast::Variant<'bundle>],
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    for variant in variants {
+        let matches = match (&variant.key, selector) {
+            (ast::VariantKey::NumberLiteral { value }, FluentValue::Number(num)) => {
+                value.parse::<f64>().map(|v| (v - num.value).abs() < f64::EPSILON).unwrap_or(false)
+            }
+            (ast::VariantKey::Identifier { name }, FluentValue::String(s)) => *name == s.as_ref(),
+            (ast::VariantKey::Identifier { name }, FluentValue::Number(num)) => {
+                let rules = match num.plural_form {
+                    intl_pluralrules::PluralRuleType::ORDINAL => scope.bundle.ordinal_plural_rules(),
+                    intl_pluralrules::PluralRuleType::CARDINAL => scope.bundle.cardinal_plural_rules(),
+                };
+                let category = rules.select(num.value).ok();
+                category.map(|c| c.to_string() == *name).unwrap_or(false)
+            }
+            _ => false,
+        };
+
+        if matches {
+            return resolve_pattern_value(&variant.value, entry, scope);
+        }
+    }
+
+    for variant in variants {
+        if variant.default {
+            return resolve_pattern_value(&variant.value, entry, scope);
+        }
+    }
+
+    scope.errors.push(ResolverError::MissingDefault);
+    FluentValue::Error()
+}
+
+fn resolve_pattern_value<'bundle>(
+    pattern: &'bundle ast::Pattern<'bundle>,
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    FluentValue::String(resolve_value_for_entry(pattern, entry, scope))
+}
+
+fn resolve_inline_expression<'bundle>(
+    expression: &'bundle ast::InlineExpression<'bundle>,
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    match expression {
+        ast::InlineExpression::StringLiteral { value } => FluentValue::String((*value).into()),
+        ast::InlineExpression::NumberLiteral { value } => (*value).into(),
+        ast::InlineExpression::VariableReference { id } => {
+            match scope.args.and_then(|args| args.get(id.name)) {
+                Some(arg) => arg.clone(),
+                None => {
+                    scope
+                        .errors
+                        .push(ResolverError::Reference(format!("${}", id.name)));
+                    FluentValue::Error()
+                }
+            }
+        }
+        ast::InlineExpression::FunctionReference { id, arguments } => {
+            let (positional, named) = resolve_call_arguments(arguments, entry, scope);
+            match scope.bundle.entries.get_entry(id.name) {
+                Some(Entry::Function(func)) => func(&positional, &named),
+                _ => {
+                    scope
+                        .errors
+                        .push(ResolverError::Reference(id.name.to_string()));
+                    FluentValue::Error()
+                }
+            }
+        }
+        ast::InlineExpression::MessageReference { id, attribute } => {
+            resolve_message_reference(id.name, attribute.as_ref().map(|a| a.name), scope)
+        }
+        ast::InlineExpression::TermReference { id, attribute, .. } => {
+            resolve_term_reference(id.name, attribute.as_ref().map(|a| a.name), scope)
+        }
+        ast::InlineExpression::Placeable { expression } => resolve_expression(expression, entry, scope),
+    }
+}
+
+fn resolve_call_arguments<'bundle>(
+    arguments: &'bundle ast::CallArguments<'bundle>,
+    entry: DisplayableNode<'bundle>,
+    scope: &mut Scope<'bundle>,
+) -> (Vec<FluentValue<'bundle>>, HashMap<&'bundle str, FluentValue<'bundle>>) {
+    let positional = arguments
+        .positional
+        .iter()
+        .map(|arg| resolve_inline_expression(arg, entry, scope))
+        .collect();
+
+    let named = arguments
+        .named
+        .iter()
+        .map(|arg| (arg.name.name, resolve_inline_expression(&arg.value, entry, scope)))
+        .collect();
+
+    (positional, named)
+}
+
+fn resolve_message_reference<'bundle>(
+    id: &'bundle str,
+    attribute: Option<&'bundle str>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    let message = match scope.bundle.entries.get_message(id) {
+        Some(message) => message,
+        None => {
+            scope.errors.push(ResolverError::Reference(id.to_string()));
+            return FluentValue::Error();
+        }
+    };
+
+    let pattern = match attribute {
+        Some(name) => message.attributes.iter().find(|attr| attr.id.name == name).map(|attr| &attr.value),
+        None => message.value.as_ref(),
+    };
+
+    match pattern {
+        Some(pattern) => with_travelled(scope, id, |scope| {
+            let entry = DisplayableNode::new(id, attribute);
+            FluentValue::String(resolve_value_for_entry(pattern, entry, scope))
+        }),
+        None => {
+            scope.errors.push(ResolverError::Reference(id.to_string()));
+            FluentValue::Error()
+        }
+    }
+}
+
+fn resolve_term_reference<'bundle>(
+    id: &'bundle str,
+    attribute: Option<&'bundle str>,
+    scope: &mut Scope<'bundle>,
+) -> FluentValue<'bundle> {
+    let term = match scope.bundle.entries.get_term(id) {
+        Some(term) => term,
+        None => {
+            scope.errors.push(ResolverError::Reference(format!("-{}", id)));
+            return FluentValue::Error();
+        }
+    };
+
+    let pattern = match attribute {
+        Some(name) => term.attributes.iter().find(|attr| attr.id.name == name).map(|attr| &attr.value),
+        None => Some(&term.value),
+    };
+
+    match pattern {
+        Some(pattern) => with_travelled(scope, id, |scope| {
+            let entry = DisplayableNode::new(id, attribute);
+            FluentValue::String(resolve_value_for_entry(pattern, entry, scope))
+        }),
+        None => {
+            scope.errors.push(ResolverError::Reference(format!("-{}", id)));
+            FluentValue::Error()
+        }
+    }
+}
+
+/// Runs `f` while `id` is marked as "currently being resolved", so that a
+/// reference cycle (`foo = a { foo } b`) is caught instead of recursing
+/// forever; on a cycle `f` is not run and the reference falls back to its
+/// id, whereas an unknown reference resolves to `???`.
+fn with_travelled<'bundle>(
+    scope: &mut Scope<'bundle>,
+    id: &'bundle str,
+    f: impl FnOnce(&mut Scope<'bundle>) -> FluentValue<'bundle>,
+) -> FluentValue<'bundle> {
+    if scope.travelled.borrow().contains(&id) {
+        scope.errors.push(ResolverError::Cyclic(id.to_string()));
+        return FluentValue::String(id.into());
+    }
+
+    scope.travelled.borrow_mut().push(id);
+    let value = f(scope);
+    scope.travelled.borrow_mut().pop();
+    value
+}
@@ -0,0 +1,47 @@
+//! Parsed FTL source, owned by value so it can be handed to one or more
+//! bundles (see `FluentBundle::add_resource`).
+
+use fluent_syntax::ast;
+use fluent_syntax::parser::{parse, ParserError};
+
+/// A parsed FTL resource. `FluentResource` owns both the original source
+/// string and the AST borrowed from it; the two are kept together so the
+/// resource can be passed around and shared (by reference, `Rc`, or `Arc`)
+/// without the caller having to manage the borrow themselves.
+#[derive(Debug)]
+pub struct FluentResource {
+    string: String,
+    ast: ast::Resource<'static>,
+}
+
+impl FluentResource {
+    /// Parses `source` as an FTL resource. On a syntax error, returns the
+    /// resource built from whatever could be recovered, alongside the list of
+    /// parser errors, so that a partially-broken resource can still be used.
+    pub fn try_new(source: String) -> Result<Self, (Self, Vec<ParserError>)> {
+        match parse(&source) {
+            Ok(ast) => {
+                // SAFETY: `ast` borrows from `source`. `source` is moved into
+                // the returned `FluentResource` alongside `ast`, unchanged for
+                // the rest of its life, so the borrow remains valid for as
+                // long as the struct exists; the 'static annotation here is
+                // then only ever exposed back out with an appropriately
+                // shortened lifetime through `ast()`.
+                let ast: ast::Resource<'static> = unsafe { std::mem::transmute(ast) };
+                Ok(FluentResource { string: source, ast })
+            }
+            Err((ast, errors)) => {
+                let ast: ast::Resource<'static> = unsafe { std::mem::transmute(ast) };
+                Err((FluentResource { string: source, ast }, errors))
+            }
+        }
+    }
+
+    pub fn ast(&self) -> &ast::Resource {
+        &self.ast
+    }
+
+    pub fn source(&self) -> &str {
+        &self.string
+    }
+}
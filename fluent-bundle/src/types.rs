@@ -0,0 +1,150 @@
+//! Types used to represent values that flow in and out of message resolution:
+//! arguments passed in by the caller, values returned by functions, and the
+//! values selected against in a `SelectExpression`.
+
+use std::borrow::Cow;
+use std::fmt;
+
+use intl_pluralrules::PluralRuleType;
+
+/// A number argument, carrying enough information for the resolver to both
+/// format it and select a plural category for it.
+///
+/// Numbers are cardinal (`1 apple`, `2 apples`) by default; call
+/// [`as_ordinal`] to mark one for ordinal category selection instead (`1st`,
+/// `2nd`, `3rd`), e.g. for messages like `{ $position -> [one] {$position}st
+/// ...}`.
+///
+/// [`as_ordinal`]: #method.as_ordinal
+#[derive(Clone, Debug, PartialEq)]
+pub struct FluentNumber {
+    pub value: f64,
+    pub plural_form: PluralRuleType,
+}
+
+impl FluentNumber {
+    pub fn new(value: f64) -> Self {
+        FluentNumber {
+            value,
+            plural_form: PluralRuleType::CARDINAL,
+        }
+    }
+
+    /// Marks this number as selecting against ordinal ("1st"/"2nd"/"3rd")
+    /// plural categories instead of the default cardinal ("1 apple"/"2
+    /// apples") ones.
+    pub fn as_ordinal(mut self) -> Self {
+        self.plural_form = PluralRuleType::ORDINAL;
+        self
+    }
+}
+
+impl fmt::Display for FluentNumber {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.value)
+    }
+}
+
+impl From<&str> for FluentNumber {
+    fn from(s: &str) -> Self {
+        FluentNumber::new(s.parse().unwrap_or(0.0))
+    }
+}
+
+impl From<String> for FluentNumber {
+    fn from(s: String) -> Self {
+        FluentNumber::new(s.parse().unwrap_or(0.0))
+    }
+}
+
+macro_rules! from_numeric {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl From<$ty> for FluentNumber {
+                fn from(n: $ty) -> Self {
+                    FluentNumber::new(n as f64)
+                }
+            }
+        )+
+    };
+}
+
+from_numeric!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// A value that can be passed as a message argument, returned from a
+/// function, or resolved from a pattern.
+#[derive(Clone, Debug, PartialEq)]
+pub enum FluentValue<'source> {
+    String(Cow<'source, str>),
+    Number(FluentNumber),
+    None(),
+    Error(),
+}
+
+impl<'source> FluentValue<'source> {
+    /// Formats this value to the string that should be substituted into a
+    /// pattern for it.
+    pub fn as_string(&self) -> Cow<'source, str> {
+        match self {
+            FluentValue::String(s) => s.clone(),
+            FluentValue::Number(n) => n.to_string().into(),
+            FluentValue::Error() => Cow::Borrowed("???"),
+            FluentValue::None() => Cow::Borrowed(""),
+        }
+    }
+}
+
+impl<'source> From<&'source str> for FluentValue<'source> {
+    fn from(s: &'source str) -> Self {
+        FluentValue::String(Cow::Borrowed(s))
+    }
+}
+
+impl<'source> From<String> for FluentValue<'source> {
+    fn from(s: String) -> Self {
+        FluentValue::String(Cow::Owned(s))
+    }
+}
+
+impl<'source> From<FluentNumber> for FluentValue<'source> {
+    fn from(n: FluentNumber) -> Self {
+        FluentValue::Number(n)
+    }
+}
+
+macro_rules! value_from_numeric {
+    ($($ty:ty),+ $(,)?) => {
+        $(
+            impl<'source> From<$ty> for FluentValue<'source> {
+                fn from(n: $ty) -> Self {
+                    FluentValue::Number(FluentNumber::new(n as f64))
+                }
+            }
+        )+
+    };
+}
+
+value_from_numeric!(i8, i16, i32, i64, isize, u8, u16, u32, u64, usize, f32, f64);
+
+/// Identifies the message (and, optionally, attribute) a value was resolved
+/// for. Errors surfaced during resolution do not record it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct DisplayableNode<'source> {
+    id: &'source str,
+    attribute: Option<&'source str>,
+}
+
+impl<'source> DisplayableNode<'source> {
+    pub fn new(id: &'source str, attribute: Option<&'source str>) -> Self {
+        DisplayableNode { id, attribute }
+    }
+}
+
+impl<'source> fmt::Display for DisplayableNode<'source> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.attribute {
+            Some(attribute) => write!(f, "{}.{}", self.id, attribute),
+            None => write!(f, "{}", self.id),
+        }
+    }
+}